@@ -1,10 +1,14 @@
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 use std::io::{self, Write};
 use std::iter::Peekable;
 
 #[derive(Debug, Clone)]
 enum Token {
-    Var(char),
+    Var(String),
+    True,    // T, true
+    False,   // F, false
     Not,     // !
     And,     // &
     Or,      // |
@@ -15,7 +19,45 @@ enum Token {
     RParen,  // )
 }
 
-fn tokenize(expr: &str) -> Vec<Token> {
+#[derive(Debug, Clone)]
+enum LexError {
+    UnexpectedChar(char),
+    IncompleteArrow,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexError::UnexpectedChar(c) => write!(f, "Unexpected character: {}", c),
+            LexError::IncompleteArrow => write!(f, "Invalid token: expected -> or <->"),
+        }
+    }
+}
+
+impl Error for LexError {}
+
+#[derive(Debug, Clone)]
+enum ParseError {
+    UnexpectedToken(Token),
+    UnexpectedEof,
+    MissingRParen,
+    TrailingInput(Token),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedToken(tok) => write!(f, "Unexpected token: {:?}", tok),
+            ParseError::UnexpectedEof => write!(f, "Unexpected end of input"),
+            ParseError::MissingRParen => write!(f, "Expected ')'"),
+            ParseError::TrailingInput(tok) => write!(f, "Unexpected trailing input: {:?}", tok),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, LexError> {
     let mut tokens = Vec::new();
     let mut chars = expr.chars().peekable();
     while let Some(&ch) = chars.peek() {
@@ -53,7 +95,7 @@ fn tokenize(expr: &str) -> Vec<Token> {
                     chars.next();
                     tokens.push(Token::Implies);
                 } else {
-                    panic!("Invalid token: expected ->");
+                    return Err(LexError::IncompleteArrow);
                 }
             }
             '<' => {
@@ -61,150 +103,464 @@ fn tokenize(expr: &str) -> Vec<Token> {
                 if chars.next() == Some('-') && chars.next() == Some('>') {
                     tokens.push(Token::Iff);
                 } else {
-                    panic!("Invalid token: expected <->");
+                    return Err(LexError::IncompleteArrow);
                 }
             }
             c if c.is_ascii_alphabetic() => {
-                tokens.push(Token::Var(c));
-                chars.next();
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                match ident.as_str() {
+                    "T" | "true" => tokens.push(Token::True),
+                    "F" | "false" => tokens.push(Token::False),
+                    _ => tokens.push(Token::Var(ident)),
+                }
+            }
+            _ => return Err(LexError::UnexpectedChar(ch)),
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinOp {
+    And,
+    Or,
+    Xor,
+    Implies,
+    Iff,
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinOp::And => "&",
+            BinOp::Or => "|",
+            BinOp::Xor => "^",
+            BinOp::Implies => "->",
+            BinOp::Iff => "<->",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Var(String),
+    Const(bool),
+    Not(Box<Expr>),
+    Bin(BinOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, vars: &HashMap<String, bool>) -> bool {
+        match self {
+            Expr::Var(name) => *vars.get(name).unwrap_or(&false),
+            Expr::Const(b) => *b,
+            Expr::Not(inner) => !inner.eval(vars),
+            Expr::Bin(op, lhs, rhs) => {
+                let lhs = lhs.eval(vars);
+                let rhs = rhs.eval(vars);
+                match op {
+                    BinOp::And => lhs & rhs,
+                    BinOp::Or => lhs | rhs,
+                    BinOp::Xor => lhs ^ rhs,
+                    BinOp::Implies => !lhs || rhs,
+                    BinOp::Iff => lhs == rhs,
+                }
+            }
+        }
+    }
+
+    /// Collects every non-trivial (`Not`/`Bin`) node in `self`, in post-order,
+    /// so each sub-formula appears after the pieces it is built from.
+    fn subexpressions(&self) -> Vec<&Expr> {
+        let mut out = Vec::new();
+        self.collect_subexpressions(&mut out);
+        out
+    }
+
+    fn collect_subexpressions<'e>(&'e self, out: &mut Vec<&'e Expr>) {
+        match self {
+            Expr::Var(_) | Expr::Const(_) => {}
+            Expr::Not(inner) => {
+                inner.collect_subexpressions(out);
+                out.push(self);
+            }
+            Expr::Bin(_, lhs, rhs) => {
+                lhs.collect_subexpressions(out);
+                rhs.collect_subexpressions(out);
+                out.push(self);
+            }
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if matches!(self, Expr::Bin(..)) {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Const(true) => write!(f, "T"),
+            Expr::Const(false) => write!(f, "F"),
+            Expr::Not(inner) => {
+                write!(f, "!")?;
+                inner.fmt_operand(f)
+            }
+            Expr::Bin(op, lhs, rhs) => {
+                lhs.fmt_operand(f)?;
+                write!(f, " {} ", op)?;
+                rhs.fmt_operand(f)
             }
-            _ => panic!("Unexpected character: {}", ch),
         }
     }
-    tokens
 }
 
 struct Parser<'a> {
     tokens: Peekable<std::slice::Iter<'a, Token>>,
-    vars: &'a HashMap<char, bool>,
 }
 
 impl<'a> Parser<'a> {
-    fn new(tokens: &'a [Token], vars: &'a HashMap<char, bool>) -> Self {
+    fn new(tokens: &'a [Token]) -> Self {
         Self {
             tokens: tokens.iter().peekable(),
-            vars,
         }
     }
 
-    fn parse_expr(&mut self) -> bool {
+    /// Parses a complete expression, erroring if any tokens are left over.
+    fn parse(&mut self) -> Result<Expr, ParseError> {
+        let result = self.parse_expr()?;
+        match self.tokens.next() {
+            Some(tok) => Err(ParseError::TrailingInput(tok.clone())),
+            None => Ok(result),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, ParseError> {
         self.parse_iff()
     }
 
-    fn parse_iff(&mut self) -> bool {
-        let mut result = self.parse_implies();
+    fn parse_iff(&mut self) -> Result<Expr, ParseError> {
+        let mut result = self.parse_implies()?;
         while let Some(Token::Iff) = self.tokens.peek() {
             self.tokens.next();
-            let rhs = self.parse_implies();
-            result = result == rhs;
+            let rhs = self.parse_implies()?;
+            result = Expr::Bin(BinOp::Iff, Box::new(result), Box::new(rhs));
         }
-        result
+        Ok(result)
     }
 
-    fn parse_implies(&mut self) -> bool {
-        let mut result = self.parse_or();
+    fn parse_implies(&mut self) -> Result<Expr, ParseError> {
+        let mut result = self.parse_or()?;
         while let Some(Token::Implies) = self.tokens.peek() {
             self.tokens.next();
-            let rhs = self.parse_or();
-            result = !result || rhs;
+            let rhs = self.parse_or()?;
+            result = Expr::Bin(BinOp::Implies, Box::new(result), Box::new(rhs));
         }
-        result
+        Ok(result)
     }
 
-    fn parse_or(&mut self) -> bool {
-        let mut result = self.parse_xor();
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut result = self.parse_xor()?;
         while let Some(Token::Or) = self.tokens.peek() {
             self.tokens.next();
-            result |= self.parse_xor();
+            let rhs = self.parse_xor()?;
+            result = Expr::Bin(BinOp::Or, Box::new(result), Box::new(rhs));
         }
-        result
+        Ok(result)
     }
 
-    fn parse_xor(&mut self) -> bool {
-        let mut result = self.parse_and();
+    fn parse_xor(&mut self) -> Result<Expr, ParseError> {
+        let mut result = self.parse_and()?;
         while let Some(Token::Xor) = self.tokens.peek() {
             self.tokens.next();
-            result ^= self.parse_and();
+            let rhs = self.parse_and()?;
+            result = Expr::Bin(BinOp::Xor, Box::new(result), Box::new(rhs));
         }
-        result
+        Ok(result)
     }
 
-    fn parse_and(&mut self) -> bool {
-        let mut result = self.parse_not();
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut result = self.parse_not()?;
         while let Some(Token::And) = self.tokens.peek() {
             self.tokens.next();
-            result &= self.parse_not();
+            let rhs = self.parse_not()?;
+            result = Expr::Bin(BinOp::And, Box::new(result), Box::new(rhs));
         }
-        result
+        Ok(result)
     }
 
-    fn parse_not(&mut self) -> bool {
+    fn parse_not(&mut self) -> Result<Expr, ParseError> {
         if let Some(Token::Not) = self.tokens.peek() {
             self.tokens.next();
-            !self.parse_not()
+            Ok(Expr::Not(Box::new(self.parse_not()?)))
         } else {
             self.parse_atom()
         }
     }
 
-    fn parse_atom(&mut self) -> bool {
+    fn parse_atom(&mut self) -> Result<Expr, ParseError> {
         match self.tokens.next() {
-            Some(Token::Var(c)) => *self.vars.get(c).unwrap_or(&false),
+            Some(Token::Var(name)) => Ok(Expr::Var(name.clone())),
+            Some(Token::True) => Ok(Expr::Const(true)),
+            Some(Token::False) => Ok(Expr::Const(false)),
             Some(Token::LParen) => {
-                let val = self.parse_expr();
+                let val = self.parse_expr()?;
                 match self.tokens.next() {
                     Some(Token::RParen) => {}
-                    _ => panic!("Expected ')'"),
+                    _ => return Err(ParseError::MissingRParen),
                 }
-                val
+                Ok(val)
             }
-            Some(tok) => panic!("Unexpected token: {:?}", tok),
-            None => panic!("Unexpected end of input"),
+            Some(tok) => Err(ParseError::UnexpectedToken(tok.clone())),
+            None => Err(ParseError::UnexpectedEof),
         }
     }
 }
 
-fn extract_variables(expr: &str) -> Vec<char> {
-    let vars: HashSet<char> = expr.chars().filter(|c| c.is_ascii_alphabetic()).collect();
-    let mut vars_vec: Vec<char> = vars.into_iter().collect();
+fn extract_variables(tokens: &[Token]) -> Vec<String> {
+    let vars: HashSet<String> = tokens
+        .iter()
+        .filter_map(|tok| match tok {
+            Token::Var(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut vars_vec: Vec<String> = vars.into_iter().collect();
     vars_vec.sort();
     vars_vec
 }
 
-fn main() {
-    print!("Please enter a boolean expression (support ! & | ^ -> <->): \n");
-    io::stdout().flush().unwrap();
+#[derive(Debug, Clone)]
+enum Classification {
+    Tautology,
+    Contradiction,
+    Satisfiable { witness: HashMap<String, bool> },
+}
 
-    let mut expr = String::new();
-    io::stdin().read_line(&mut expr).unwrap();
-    let expr = expr.trim();
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Classification::Tautology => write!(f, "Tautology"),
+            Classification::Contradiction => write!(f, "Contradiction"),
+            Classification::Satisfiable { witness } => {
+                let mut assignment: Vec<(&String, &bool)> = witness.iter().collect();
+                assignment.sort_by_key(|(var, _)| (*var).clone());
+                write!(f, "Satisfiable, e.g. ")?;
+                for (i, (var, val)) in assignment.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}={}", var, if **val { 1 } else { 0 })?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
 
-    // let expr = "((A & B) -> (!C | D)) <-> (!A | (C & (D -> B)))"; // test expression
+/// The proper, distinct sub-expressions of `ast`, in the order they should be
+/// shown as table columns (root excluded; that column is printed as `Result`).
+fn distinct_subexpressions(ast: &Expr) -> Vec<&Expr> {
+    let mut distinct: Vec<&Expr> = Vec::new();
+    for sub in ast.subexpressions() {
+        if std::ptr::eq(sub, ast) {
+            continue;
+        }
+        let text = sub.to_string();
+        if !distinct.iter().any(|d| d.to_string() == text) {
+            distinct.push(sub);
+        }
+    }
+    distinct
+}
 
-    let tokens = tokenize(&expr);
-    let variables = extract_variables(&expr);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Plain,
+    Csv,
+    Markdown,
+}
 
-    if variables.len() > 5 {
-        println!("Error: More than 5 variables are not supported.");
-        return;
+impl OutputFormat {
+    fn parse(s: &str) -> Option<OutputFormat> {
+        match s.to_lowercase().as_str() {
+            "plain" => Some(OutputFormat::Plain),
+            "csv" => Some(OutputFormat::Csv),
+            "markdown" | "md" => Some(OutputFormat::Markdown),
+            _ => None,
+        }
     }
+}
 
-    for var in &variables {
-        print!("{} ", var);
+/// Reads `--format <plain|csv|markdown>` (or `--format=<value>`) from the
+/// process arguments, if present.
+fn format_from_args() -> Option<OutputFormat> {
+    let args: Vec<String> = std::env::args().collect();
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(value) = arg.strip_prefix("--format=") {
+            return OutputFormat::parse(value);
+        }
+        if arg == "--format" {
+            return args.get(i + 1).and_then(|v| OutputFormat::parse(v));
+        }
     }
-    println!("| Result");
+    None
+}
+
+fn prompt_format() -> OutputFormat {
+    print!("Output format (plain/csv/markdown) [plain]: ");
+    io::stdout().flush().unwrap();
+
+    let mut choice = String::new();
+    io::stdin().read_line(&mut choice).unwrap();
+    let choice = choice.trim();
+
+    if choice.is_empty() {
+        OutputFormat::Plain
+    } else {
+        OutputFormat::parse(choice).unwrap_or(OutputFormat::Plain)
+    }
+}
 
-    let total = 1 << variables.len();
+/// Renders a table of `0`/`1` rows under `headers` (the last header is the
+/// final `Result` column) according to `format`.
+fn render(format: OutputFormat, headers: &[String], rows: &[Vec<bool>]) {
+    let cell = |b: bool| if b { "1" } else { "0" };
 
-    for i in 0..total {
-        let mut var_map = HashMap::new();
-        for (j, var) in variables.iter().enumerate() {
-            let val = (i >> (variables.len() - j - 1)) & 1 == 1;
-            var_map.insert(*var, val);
-            print!("{} ", if val { 1 } else { 0 });
+    match format {
+        OutputFormat::Plain => {
+            let (cols, result) = headers.split_at(headers.len() - 1);
+            if cols.is_empty() {
+                println!("{}", result[0]);
+            } else {
+                println!("{} | {}", cols.join(" "), result[0]);
+            }
+            for row in rows {
+                let (vals, result) = row.split_at(row.len() - 1);
+                if vals.is_empty() {
+                    println!("{}", cell(result[0]));
+                } else {
+                    let vals: Vec<&str> = vals.iter().map(|&b| cell(b)).collect();
+                    println!("{} | {}", vals.join(" "), cell(result[0]));
+                }
+            }
         }
-        let mut parser = Parser::new(&tokens, &var_map);
-        let result = parser.parse_expr();
-        println!("| {}", if result { 1 } else { 0 });
+        OutputFormat::Csv => {
+            println!("{}", headers.join(","));
+            for row in rows {
+                let vals: Vec<&str> = row.iter().map(|&b| cell(b)).collect();
+                println!("{}", vals.join(","));
+            }
+        }
+        OutputFormat::Markdown => {
+            println!("| {} |", headers.join(" | "));
+            println!("|{}|", vec!["---"; headers.len()].join("|"));
+            for row in rows {
+                let vals: Vec<&str> = row.iter().map(|&b| cell(b)).collect();
+                println!("| {} |", vals.join(" | "));
+            }
+        }
+    }
+}
+
+fn main() {
+    let format = format_from_args().unwrap_or_else(prompt_format);
+
+    loop {
+        print!("Please enter a boolean expression (support ! & | ^ -> <->): \n");
+        io::stdout().flush().unwrap();
+
+        let mut expr = String::new();
+        let bytes_read = io::stdin().read_line(&mut expr).unwrap();
+        if bytes_read == 0 {
+            break;
+        }
+        let expr = expr.trim();
+
+        let tokens = match tokenize(expr) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                println!("Error: {}", e);
+                continue;
+            }
+        };
+        let variables = extract_variables(&tokens);
+
+        if variables.len() > 5 {
+            println!("Error: More than 5 variables are not supported.");
+            continue;
+        }
+
+        let ast = match Parser::new(&tokens).parse() {
+            Ok(ast) => ast,
+            Err(e) => {
+                println!("Error: {}", e);
+                continue;
+            }
+        };
+
+        let subexprs = distinct_subexpressions(&ast);
+
+        let mut headers: Vec<String> = variables.clone();
+        headers.extend(subexprs.iter().map(|sub| sub.to_string()));
+        headers.push("Result".to_string());
+
+        let total = 1 << variables.len();
+
+        let mut rows = Vec::with_capacity(total);
+        let mut any_true = false;
+        let mut any_false = false;
+        let mut witness = None;
+
+        for i in 0..total {
+            let mut var_map = HashMap::new();
+            let mut row = Vec::with_capacity(headers.len());
+            for (j, var) in variables.iter().enumerate() {
+                let val = (i >> (variables.len() - j - 1)) & 1 == 1;
+                var_map.insert(var.clone(), val);
+                row.push(val);
+            }
+            for sub in &subexprs {
+                row.push(sub.eval(&var_map));
+            }
+            let result = ast.eval(&var_map);
+            row.push(result);
+            rows.push(row);
+
+            if result {
+                any_true = true;
+                witness.get_or_insert_with(|| var_map.clone());
+            } else {
+                any_false = true;
+            }
+        }
+
+        render(format, &headers, &rows);
+
+        let classification = if !any_false {
+            Classification::Tautology
+        } else if !any_true {
+            Classification::Contradiction
+        } else {
+            Classification::Satisfiable {
+                witness: witness.unwrap(),
+            }
+        };
+        println!("{}", classification);
+        break;
     }
 }